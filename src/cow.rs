@@ -0,0 +1,141 @@
+use core::fmt::{self, Display};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use super::{URI, User, UriBuf};
+
+/// URI with copy-on-write components, created by `URI::to_cow`
+///
+/// Components are borrowed from parsed string (no allocation) until they are replaced,
+/// so only changed components are allocated. `CowUri<'a>` cannot outlive parsed string,
+/// use `into_owned` to get `CowUri<'static>`, which copies remaining borrowed components.
+#[derive(Debug,Clone,PartialEq,Eq,Hash,Default)]
+pub struct CowUri<'a> {
+    pub scheme: Option<Cow<'a, str>>,
+    pub user: Option<Cow<'a, str>>,
+    pub password: Option<Cow<'a, str>>,
+    pub host: Option<Cow<'a, str>>,
+    pub zone_id: Option<Cow<'a, str>>,
+    pub port: Option<u16>,
+    pub path: Option<Cow<'a, str>>,
+    pub opaque: Option<Cow<'a, str>>,
+    pub query: Option<Vec<(Cow<'a, str>, Cow<'a, str>)>>,
+    pub hash: Option<Cow<'a, str>>
+}
+
+fn owned<'a>(c: Option<Cow<'a, str>>) -> Option<Cow<'static, str>> {
+    c.map(|s| Cow::Owned(s.into_owned()))
+}
+
+fn into_string(c: Option<Cow<'_, str>>) -> Option<String> {
+    c.map(|s| s.into_owned())
+}
+
+impl <'a> CowUri<'a> {
+    /// Copies all still borrowed components, so result does not depend on parsed string
+    pub fn into_owned(self) -> CowUri<'static> {
+        CowUri {
+            scheme: owned(self.scheme),
+            user: owned(self.user),
+            password: owned(self.password),
+            host: owned(self.host),
+            zone_id: owned(self.zone_id),
+            port: self.port,
+            path: owned(self.path),
+            opaque: owned(self.opaque),
+            query: self.query.map(|q| q.into_iter()
+                .map(|(k, v)| (Cow::Owned(k.into_owned()), Cow::Owned(v.into_owned()))).collect()),
+            hash: owned(self.hash)
+        }
+    }
+
+    /// True if no component is allocated (all point into parsed string)
+    pub fn is_borrowed(&self) -> bool {
+        fn borrowed(c: &Option<Cow<'_, str>>) -> bool {
+            !matches!(*c, Some(Cow::Owned(_)))
+        }
+        borrowed(&self.scheme) && borrowed(&self.user) && borrowed(&self.password) && borrowed(&self.host)
+            && borrowed(&self.zone_id) && borrowed(&self.path) && borrowed(&self.opaque) && borrowed(&self.hash)
+            && self.query.as_ref().map(|q| q.iter().all(|(k, v)| {
+                matches!((k, v), (Cow::Borrowed(_), Cow::Borrowed(_)))
+            })).unwrap_or(true)
+    }
+
+    /// Borrows this `CowUri` as `URI`, password is used only if user is present
+    pub fn as_uri(&self) -> URI<'_> {
+        URI {
+            scheme: self.scheme.as_deref(),
+            user: self.user.as_deref().map(|name| User {
+                name,
+                password: self.password.as_deref()
+            }),
+            host: self.host.as_deref(),
+            zone_id: self.zone_id.as_deref(),
+            port: self.port,
+            port_name: None,
+            raw_authority: None,
+            path: self.path.as_deref(),
+            opaque: self.opaque.as_deref(),
+            query: self.query.as_ref().map(|q| q.iter().map(|(k, v)| (k.as_ref(), v.as_ref())).collect()),
+            raw_query: None,
+            hash: self.hash.as_deref()
+        }
+    }
+}
+
+impl <'a> From<CowUri<'a>> for UriBuf {
+    fn from(u: CowUri<'a>) -> Self {
+        UriBuf {
+            scheme: into_string(u.scheme),
+            user: into_string(u.user),
+            password: into_string(u.password),
+            host: into_string(u.host),
+            zone_id: into_string(u.zone_id),
+            port: u.port,
+            path: into_string(u.path),
+            opaque: into_string(u.opaque),
+            query: u.query.map(|q| q.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect()),
+            hash: into_string(u.hash)
+        }
+    }
+}
+
+impl <'a> Display for CowUri<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_uri().write_to(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::parse_uri;
+
+    #[test]
+    fn test_borrowed() {
+        let s = "http://user:pw@h:8080/a/b?x=1&y=2#f";
+        let us = parse_uri(s).unwrap();
+        let c = us.to_cow();
+        assert!(c.is_borrowed());
+        assert_eq!(c.host, Some(Cow::Borrowed("h")));
+        assert_eq!(c.as_uri(), us);
+        assert_eq!(c.to_string(), s);
+    }
+
+    #[test]
+    fn test_owned() {
+        let s = String::from("http://h/a/b?x=1#f");
+        let mut c = parse_uri(&s).unwrap().to_cow();
+        c.path = Some(Cow::Owned("/c".to_owned()));
+        assert!(!c.is_borrowed());
+        assert_eq!(c.host, Some(Cow::Borrowed("h")));
+        assert_eq!(c.to_string(), "http://h/c?x=1#f");
+
+        let c = c.into_owned();
+        drop(s);
+        assert_eq!(c.to_string(), "http://h/c?x=1#f");
+        let u: UriBuf = c.into();
+        assert_eq!(u.path, Some("/c".to_owned()));
+        assert_eq!(u.host, Some("h".to_owned()));
+    }
+}
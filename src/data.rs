@@ -0,0 +1,143 @@
+use core::str;
+use alloc::vec::Vec;
+use super::{Error, percent};
+
+/// Parsed `data:` URI (RFC 2397)
+/// Empty media type defaults to `text/plain;charset=US-ASCII`
+#[derive(Debug,PartialEq,Clone)]
+pub struct DataUri<'a> {
+    pub media_type: &'a str,
+    pub params: Vec<(&'a str, &'a str)>,
+    pub base64: bool,
+    /// raw (still encoded) payload
+    pub data: &'a str
+}
+
+impl <'a> DataUri<'a> {
+    /// Decodes payload - either base64 or percent-encoded
+    pub fn decode(&self) -> Result<Vec<u8>, Error> {
+        let bytes = percent::decode_bytes(self.data, false)?;
+        if self.base64 {
+            decode_base64(&bytes)
+        } else {
+            Ok(bytes)
+        }
+    }
+}
+
+/// Parses `data:` URI like `data:text/plain;charset=utf-8;base64,SGVsbG8=`
+pub fn parse_data_uri<T: AsRef<[u8]>+?Sized>(uri_string: &T) -> Result<DataUri<'_>, Error> {
+    let s = str::from_utf8(uri_string.as_ref()).map_err(|_| Error::InvalidDataUri)?;
+    if s.len() < 5 || !s.as_bytes()[..5].eq_ignore_ascii_case(b"data:") {
+        return Err(Error::InvalidDataUri)
+    }
+    let comma = s.find(',').ok_or(Error::InvalidDataUri)?;
+    let mut header = s[5..comma].split(';');
+    let media_type = header.next().unwrap_or("");
+    let mut params = vec![];
+    let mut base64 = false;
+    for param in header {
+        if param.eq_ignore_ascii_case("base64") {
+            base64 = true;
+        } else {
+            match param.find('=') {
+                Some(i) => params.push((&param[..i], &param[i+1..])),
+                None => return Err(Error::InvalidDataUri)
+            }
+        }
+    }
+    let media_type = if media_type.is_empty() {
+        if !params.iter().any(|&(k, _)| k.eq_ignore_ascii_case("charset")) {
+            params.insert(0, ("charset", "US-ASCII"));
+        }
+        "text/plain"
+    } else {
+        media_type
+    };
+    Ok(DataUri {media_type, params, base64, data: &s[comma+1..]})
+}
+
+fn base64_value(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None
+    }
+}
+
+fn decode_base64(input: &[u8]) -> Result<Vec<u8>, Error> {
+    let input = match input.iter().position(|&c| c == b'=') {
+        Some(pad) => {
+            if pad + 2 < input.len() || input[pad..].iter().any(|&c| c != b'=') {
+                return Err(Error::InvalidEncoding)
+            }
+            &input[..pad]
+        },
+        None => input
+    };
+    let mut res = Vec::with_capacity(input.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for &c in input {
+        let v = base64_value(c).ok_or(Error::InvalidEncoding)?;
+        acc = acc << 6 | u32::from(v);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            res.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    if bits >= 6 {
+        return Err(Error::InvalidEncoding)
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        let u = parse_data_uri("data:text/plain;base64,SGVsbG8=").unwrap();
+        assert_eq!(u.media_type, "text/plain");
+        assert!(u.params.is_empty());
+        assert!(u.base64);
+        assert_eq!(u.data, "SGVsbG8=");
+        assert_eq!(u.decode().unwrap(), b"Hello");
+
+        let u = parse_data_uri("data:image/png;charset=utf-8;base64,SGVsbG8gV29ybGQ").unwrap();
+        assert_eq!(u.media_type, "image/png");
+        assert_eq!(u.params, vec![("charset", "utf-8")]);
+        assert_eq!(u.decode().unwrap(), b"Hello World");
+
+        assert_eq!(parse_data_uri("data:;base64,SGV*").unwrap().decode(), Err(Error::InvalidEncoding));
+        assert_eq!(parse_data_uri("data:;base64,S").unwrap().decode(), Err(Error::InvalidEncoding));
+    }
+
+    #[test]
+    fn test_percent_encoded() {
+        let u = parse_data_uri("data:,Hello%2C%20World!").unwrap();
+        assert_eq!(u.media_type, "text/plain");
+        assert_eq!(u.params, vec![("charset", "US-ASCII")]);
+        assert!(!u.base64);
+        assert_eq!(u.decode().unwrap(), b"Hello, World!");
+
+        let u = parse_data_uri("DATA:;charset=utf-8,caf%C3%A9").unwrap();
+        assert_eq!(u.media_type, "text/plain");
+        assert_eq!(u.params, vec![("charset", "utf-8")]);
+        assert_eq!(u.decode().unwrap(), "café".as_bytes());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(parse_data_uri("http://h/"), Err(Error::InvalidDataUri));
+        assert_eq!(parse_data_uri("dat€,x"), Err(Error::InvalidDataUri));
+        assert_eq!(parse_data_uri("data:text/plain"), Err(Error::InvalidDataUri));
+        assert_eq!(parse_data_uri("data:text/plain;foo,x"), Err(Error::InvalidDataUri));
+    }
+}
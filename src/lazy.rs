@@ -0,0 +1,233 @@
+use core::str;
+use super::{URI, User, Error, Component, SchemeRegistry, parser, parse_uri, parse_error, utf8_error};
+
+/// URI split to raw components, which are parsed only when accessed, created by `parse_uri_lazy`
+///
+/// Splitting (as in RFC 3986 appendix B) does not allocate, so it's cheap when only
+/// some components (like host) are needed. Authority is parsed by each of `user`, `host`
+/// and `port` and query pairs are scanned by `query_pairs`, use `to_uri` to parse everything.
+/// Scheme validation is skipped until `to_uri`, so URI like bare `http://` (rejected by `parse_uri`)
+/// is split and its `host` is empty. Drive letter rule of built-in handlers is applied when splitting,
+/// so `file://C:/x` has empty authority and path `C:/x` as in `parse_uri`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct LazyUri<'a> {
+    input: &'a str,
+    scheme: &'a str,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>
+}
+
+impl <'a> LazyUri<'a> {
+    /// Whole parsed string
+    pub fn as_str(&self) -> &'a str {
+        self.input
+    }
+
+    /// Scheme (without trailing `:`), it's checked by `parse_uri_lazy`
+    pub fn scheme(&self) -> &'a str {
+        self.scheme
+    }
+
+    /// Raw authority (between `//` and path), `None` if there is no authority
+    pub fn authority(&self) -> Option<&'a str> {
+        self.authority
+    }
+
+    /// Parses authority to user, host (IPv6 without brackets), zone id and port
+    pub fn authority_parts(&self) -> Result<Option<parser::AuthorityParts<'a>>, Error> {
+        let authority = match self.authority {
+            Some(a) => a,
+            None => return Ok(None)
+        };
+        if authority.is_empty() {
+            return Ok(Some((None, "", None, None)))
+        }
+        let b = authority.as_bytes();
+        match parser::authority_body(b) {
+            Ok(([], parts)) => Ok(Some(parts)),
+            Ok((rest, _)) => Err(Error::NotFullyParsed{offset: self.offset(b.len() - rest.len())}),
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(parse_error(b, e, Component::Host)),
+            Err(nom::Err::Incomplete(_)) => Err(Error::Incomplete)
+        }
+    }
+
+    /// Parses authority and returns user info, `None` if there is no authority or user
+    pub fn user(&self) -> Result<Option<User<'a>>, Error> {
+        self.authority_parts().map(|a| a.and_then(|a| a.0))
+    }
+
+    /// Parses authority and returns host (IPv6 without brackets), `None` if there is no authority,
+    /// empty authority (like in `file:///etc/hosts` or `file://C:/x`) gives empty host
+    pub fn host(&self) -> Result<Option<&'a str>, Error> {
+        self.authority_parts().map(|a| a.map(|a| a.1))
+    }
+
+    /// Parses authority and returns port, `None` if there is no authority or port
+    pub fn port(&self) -> Result<Option<u16>, Error> {
+        self.authority_parts().map(|a| a.and_then(|a| a.3))
+    }
+
+    /// Raw path (or opaque part), can be empty
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Raw query without leading `?`
+    pub fn raw_query(&self) -> Option<&'a str> {
+        self.query
+    }
+
+    /// Iterates query parameters without allocation, they're scanned by same grammar as in `parse_uri`
+    /// (with default `ParseOptions`). Query is not validated, so scanning stops where `parse_uri` fails on
+    /// empty key: `?=1` gives no pairs and `?a&&b` gives only `a`, `to_uri` reports such error
+    pub fn query_pairs(&self) -> impl Iterator<Item=(&'a str, &'a str)> {
+        parser::QueryItems::new(self.query.unwrap_or(""), false)
+    }
+
+    /// Raw fragment without leading `#`
+    pub fn fragment(&self) -> Option<&'a str> {
+        self.fragment
+    }
+
+    /// Parses whole URI by `parse_uri`
+    pub fn to_uri(&self) -> Result<URI<'a>, Error> {
+        parse_uri(self.input)
+    }
+
+    // offset in input of authority offset
+    fn offset(&self, authority_offset: usize) -> usize {
+        self.scheme.len() + 3 + authority_offset
+    }
+}
+
+/// Splits URI to raw components without parsing them, only scheme is checked.
+/// Components are parsed on demand by `LazyUri` methods, so it's fast path
+/// when only few components are needed. Scheme handlers are not run (they need parsed URI),
+/// so URI rejected by `parse_uri` (like `http:foo`) can be split, use `to_uri` to validate it
+///
+/// ```
+/// use uri_parser::parse_uri_lazy;
+///
+/// let u = parse_uri_lazy("https://user@www.example.com:8443/p?a=1&b=2#f").unwrap();
+/// assert_eq!(u.scheme(), "https");
+/// assert_eq!(u.host().unwrap(), Some("www.example.com"));
+/// assert_eq!(u.query_pairs().nth(1), Some(("b", "2")));
+/// ```
+pub fn parse_uri_lazy<T: AsRef<[u8]>+?Sized>(uri_string: &T) -> Result<LazyUri<'_>, Error> {
+    let b:&[u8] = uri_string.as_ref();
+    let input = match str::from_utf8(b) {
+        Ok(s) => s,
+        Err(_) => return Err(utf8_error(b).unwrap_or(Error::InvalidEncoding))
+    };
+    let find = |from: usize, delims: &[u8]| b[from..].iter().position(|c| delims.contains(c))
+        .map(|p| p + from).unwrap_or(b.len());
+    let scheme_end = find(0, b":/?#");
+    if scheme_end == b.len() || b[scheme_end] != b':' {
+        return Err(Error::MissingScheme)
+    }
+    match parser::scheme(&b[..scheme_end]) {
+        Ok(([], _)) => (),
+        Ok((rest, _)) => return Err(Error::NotFullyParsed{offset: scheme_end - rest.len()}),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => return Err(parse_error(b, e, Component::Scheme)),
+        Err(nom::Err::Incomplete(_)) => return Err(Error::Incomplete)
+    }
+    let mut pos = scheme_end + 1;
+    let scheme = &input[..scheme_end];
+    let authority = if b[pos..].starts_with(b"//")
+        && parser::is_drive(&b[pos+2..]) && parser::drive_letter(Some(scheme), &SchemeRegistry::new()) {
+        // drive letter in place of authority is path with empty authority
+        pos += 2;
+        Some("")
+    } else if b[pos..].starts_with(b"//") {
+        let end = find(pos + 2, b"/?#");
+        let a = &input[pos+2..end];
+        pos = end;
+        Some(a)
+    } else {
+        None
+    };
+    let path_end = find(pos, b"?#");
+    let query_end = find(path_end, b"#");
+    Ok(LazyUri {
+        input,
+        scheme,
+        authority,
+        path: &input[pos..path_end],
+        query: if path_end < query_end {Some(&input[path_end+1..query_end])} else {None},
+        fragment: if query_end < b.len() {Some(&input[query_end+1..])} else {None}
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy() {
+        let s = "http://u:p@www.example.com:8080/a/b?x=1&y=2&x=3#frag";
+        let u = parse_uri_lazy(s).unwrap();
+        assert_eq!(u.scheme(), "http");
+        assert_eq!(u.authority(), Some("u:p@www.example.com:8080"));
+        assert_eq!(u.user().unwrap(), Some(User{name: "u", password: Some("p")}));
+        assert_eq!(u.host().unwrap(), Some("www.example.com"));
+        assert_eq!(u.port().unwrap(), Some(8080));
+        assert_eq!(u.path(), "/a/b");
+        assert_eq!(u.raw_query(), Some("x=1&y=2&x=3"));
+        assert_eq!(u.query_pairs().collect::<Vec<_>>(), vec![("x", "1"), ("y", "2"), ("x", "3")]);
+        assert_eq!(u.fragment(), Some("frag"));
+        assert_eq!(u.to_uri().unwrap(), parse_uri(s).unwrap());
+
+        let u = parse_uri_lazy("mailto:a@b.com").unwrap();
+        assert_eq!(u.host().unwrap(), None);
+        assert_eq!(u.path(), "a@b.com");
+        assert_eq!(u.raw_query(), None);
+        assert_eq!(parse_uri_lazy("file:///etc").unwrap().host().unwrap(), Some(""));
+        assert_eq!(parse_uri_lazy("http://[::1]:80/").unwrap().host().unwrap(), Some("::1"));
+    }
+
+    #[test]
+    fn test_lazy_errors() {
+        assert_eq!(parse_uri_lazy("//h/p"), Err(Error::MissingScheme));
+        assert!(parse_uri_lazy("1x://h/").is_err());
+        let u = parse_uri_lazy("http://h:99999/").unwrap();
+        assert_eq!(u.port(), Err(Error::InvalidPort{port: "99999".to_owned()}));
+        assert_eq!(parse_uri_lazy("http://h[x/").unwrap().host(), Err(Error::NotFullyParsed{offset: 8}));
+        assert_eq!(parse_uri_lazy(b"http://h/\xff"), Err(Error::InvalidUtf8{component: Component::Path}));
+        let u = parse_uri_lazy("http:foo").unwrap();
+        assert_eq!(u.path(), "foo");
+        assert_eq!(u.to_uri(), Err(Error::InvalidHost));
+        let u = parse_uri_lazy("http://").unwrap();
+        assert_eq!(u.authority(), Some(""));
+        assert_eq!(u.host(), Ok(Some("")));
+        assert_eq!(u.to_uri(), Err(Error::InvalidHost));
+    }
+
+    #[test]
+    fn test_lazy_drive_letter() {
+        for &s in ["file://C:/x", "FILE://c:", "file://C:?q=1", "file:///C:/x", "file://server/C:/x"].iter() {
+            let lazy = parse_uri_lazy(s).unwrap();
+            let u = parse_uri(s).unwrap();
+            assert_eq!((lazy.host().unwrap(), lazy.path()), (u.host, u.path.unwrap_or("")), "{}", s);
+            assert_eq!(lazy.to_uri(), Ok(u));
+        }
+        let u = parse_uri_lazy("file://C:/x").unwrap();
+        assert_eq!((u.authority(), u.host(), u.path()), (Some(""), Ok(Some("")), "C:/x"));
+        // only for schemes with drive letter rule
+        assert_eq!(parse_uri_lazy("s3://C:/x").unwrap().host(), Ok(Some("C")));
+    }
+
+    #[test]
+    fn test_lazy_query_pairs() {
+        let pairs = |s| parse_uri_lazy(s).unwrap().query_pairs().collect::<Vec<_>>();
+        for &s in ["http://h/?a=1&b&c=&d=x=y#f", "http://h/?", "http://h/", "http://h/?items[]=1&k=v:w@z/?"].iter() {
+            assert_eq!(pairs(s), parse_uri(s).unwrap().query.unwrap_or_default());
+        }
+        assert_eq!(pairs("http://h/?=1"), vec![]);
+        assert!(parse_uri("http://h/?=1").is_err());
+        assert_eq!(pairs("http://h/?a&&b"), vec![("a", "")]);
+        assert!(parse_uri("http://h/?a&&b").is_err());
+        assert_eq!(pairs("http://h/?a=1;b=2"), vec![("a", "1;b=2")]);
+    }
+}
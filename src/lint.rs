@@ -0,0 +1,92 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use super::{URI, Error, parse_uri, default_port};
+
+/// Non-fatal issue of parsed URI reported by `parse_uri_lint`
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum Lint {
+    /// Query parameter without value (like `?flag` or `?a=`)
+    EmptyQueryValue{key: String},
+    /// Scheme contains uppercase letters (canonical form is lowercase)
+    UppercaseScheme,
+    /// Port is same as default port of the scheme (like `http://h:80/`)
+    ExplicitDefaultPort{port: u16},
+    /// User info is present in `http` or `https` URI (deprecated by RFC 7230)
+    UserInfo
+}
+
+fn lints(u: &URI) -> Vec<Lint> {
+    let mut res = vec![];
+    if u.scheme.map(|s| s.bytes().any(|c| c.is_ascii_uppercase())).unwrap_or(false) {
+        res.push(Lint::UppercaseScheme);
+    }
+    if u.user.is_some() && (u.scheme_eq_ignore_case("http") || u.scheme_eq_ignore_case("https")) {
+        res.push(Lint::UserInfo);
+    }
+    if let Some(port) = u.port {
+        if u.scheme.and_then(default_port) == Some(port) {
+            res.push(Lint::ExplicitDefaultPort{port});
+        }
+    }
+    if let Some(ref query) = u.query {
+        res.extend(query.iter()
+            .filter(|&&(_, v)| v.is_empty())
+            .map(|&(k, _)| Lint::EmptyQueryValue{key: k.to_owned()}));
+    }
+    res
+}
+
+/// Parses URI like `parse_uri` and reports advisory issues, which don't make parsing fail,
+/// lints are empty if parsing fails
+///
+/// ```
+/// use uri_parser::{parse_uri_lint, Lint};
+///
+/// let (u, lints) = parse_uri_lint("HTTP://h:80/");
+/// assert!(u.is_ok());
+/// assert_eq!(lints, vec![Lint::UppercaseScheme, Lint::ExplicitDefaultPort{port: 80}]);
+/// ```
+pub fn parse_uri_lint<T: AsRef<[u8]>+?Sized>(uri_string: &T) -> (Result<URI<'_>, Error>, Vec<Lint>) {
+    match parse_uri(uri_string) {
+        Ok(u) => {
+            let lints = lints(&u);
+            (Ok(u), lints)
+        },
+        Err(e) => (Err(e), vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(s: &str) -> Vec<Lint> {
+        let (u, lints) = parse_uri_lint(s);
+        assert!(u.is_ok());
+        lints
+    }
+
+    #[test]
+    fn test_lints() {
+        assert_eq!(lint("http://h:80/"), vec![Lint::ExplicitDefaultPort{port: 80}]);
+        assert_eq!(lint("wss://h:443/"), vec![Lint::ExplicitDefaultPort{port: 443}]);
+        assert_eq!(lint("Https://h/"), vec![Lint::UppercaseScheme]);
+        assert_eq!(lint("http://u:p@h/"), vec![Lint::UserInfo]);
+        assert_eq!(lint("HTTPS://u@h/"), vec![Lint::UppercaseScheme, Lint::UserInfo]);
+        assert!(lint("ftp://user@h/").is_empty());
+        assert_eq!(lint("http://h/?a=1&flag&b="), vec![
+            Lint::EmptyQueryValue{key: "flag".to_owned()},
+            Lint::EmptyQueryValue{key: "b".to_owned()}
+        ]);
+        assert!(lint("http://h:8080/p?a=1").is_empty());
+        assert!(lint("mailto:a@b.com").is_empty());
+    }
+
+    #[test]
+    fn test_error() {
+        let (u, lints) = parse_uri_lint("HTTP://h:99999/");
+        assert!(u.is_err());
+        assert!(lints.is_empty());
+    }
+}
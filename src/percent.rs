@@ -0,0 +1,322 @@
+use core::fmt::{self, Display};
+use alloc::string::String;
+use alloc::vec::Vec;
+use super::Error;
+
+// delimiters which have to be escaped when writing respective component,
+// so it's parsed back unchanged
+pub const USER: &[u8] = b":@/?#[]";
+pub const PASSWORD: &[u8] = b"@/?#[]";
+pub const HOST: &[u8] = b":/?#[]@";
+pub const PATH: &[u8] = b"?#[]";
+// first segment of relative path, where `:` would make it scheme
+pub const PATH_NOSCHEME: &[u8] = b":?#[]";
+pub const OPAQUE: &[u8] = b"?#";
+pub const QUERY_KEY: &[u8] = b"&=#";
+pub const QUERY_VALUE: &[u8] = b"&#";
+// fragment runs to end of URI, so only spaces and controls are escaped
+pub const FRAGMENT: &[u8] = b"";
+
+/// Set of characters, which are left unencoded by `encode_component`
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum EncodeSet {
+    /// Path segment - `/` is encoded too
+    Path,
+    /// Query key or value - `&`, `=`, `+`, `;` and `#` are encoded
+    Query,
+    /// Fragment
+    Fragment,
+    /// User name or password - `:` (password separator) and `@` are encoded
+    UserInfo
+}
+
+impl EncodeSet {
+    // allowed characters besides unreserved ones
+    fn allowed(self) -> &'static [u8] {
+        match self {
+            EncodeSet::Path => b"!$&'()*+,;=:@",
+            EncodeSet::Query => b"!$'()*,:/?@",
+            EncodeSet::Fragment => b"!$&'()*+,;=:/?@",
+            EncodeSet::UserInfo => b"!$&'()*+,;="
+        }
+    }
+}
+
+/// Normalizes percent-encoding (RFC 3986 section 6.2.2.2) - hex digits of escapes are uppercased
+/// and escaped unreserved characters are decoded, malformed escapes are kept as they are
+pub fn normalize(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut res = String::with_capacity(b.len());
+    let mut start = 0;
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%' && i + 2 < b.len() {
+            if let (Some(h), Some(l)) = (hex_value(b[i+1]), hex_value(b[i+2])) {
+                res.push_str(&s[start..i]);
+                let c = h << 4 | l;
+                if c.is_ascii_alphanumeric() || b"-._~".contains(&c) {
+                    res.push(c as char);
+                } else {
+                    res.push_str(&format!("%{:02X}", c));
+                }
+                i += 3;
+                start = i;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    res.push_str(&s[start..]);
+    res
+}
+
+/// Percent-encodes string for use as URI component - unreserved characters (RFC 3986 section 2.3)
+/// and safe characters of given set are kept, all other bytes (including `%` and non-ASCII UTF-8 bytes)
+/// are encoded as `%XX`. `:` is kept in path, query and fragment (it's `pchar`), so path segment
+/// must not be first segment of relative reference, where `:` would end scheme
+///
+/// ```
+/// use uri_parser::{encode_component, EncodeSet};
+///
+/// assert_eq!(encode_component("a b&c", EncodeSet::Query), "a%20b%26c");
+/// assert_eq!(encode_component("10:30", EncodeSet::Path), "10:30");
+/// ```
+pub fn encode_component(s: &str, set: EncodeSet) -> String {
+    let allowed = set.allowed();
+    let mut res = String::with_capacity(s.len());
+    for &c in s.as_bytes() {
+        if c.is_ascii_alphanumeric() || b"-._~".contains(&c) || allowed.contains(&c) {
+            res.push(c as char);
+        } else {
+            res.push_str(&format!("%{:02X}", c));
+        }
+    }
+    res
+}
+
+/// Encodes plain text for storing in component of `UriBuf` - `%`, delimiters, spaces and control characters
+/// are escaped, so `Escaped` writes result unchanged and it's parsed back to same string
+pub fn encode(s: &str, delims: &[u8]) -> String {
+    let mut res = String::with_capacity(s.len());
+    for (i, c) in s.char_indices() {
+        let b = s.as_bytes()[i];
+        if c.is_ascii() && (b == b'%' || b <= b' ' || b == 0x7f || delims.contains(&b)) {
+            res.push_str(&format!("%{:02X}", b));
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+/// Writes string with escaped delimiters, spaces and control characters,
+/// `%` is never escaped, so stored (already encoded) component is written unchanged
+pub struct Escaped<'a>(pub &'a str, pub &'a [u8]);
+
+impl <'a> Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut start = 0;
+        for (i, &c) in self.0.as_bytes().iter().enumerate() {
+            if c <= b' ' || c == 0x7f || self.1.contains(&c) {
+                f.write_str(&self.0[start..i])?;
+                write!(f, "%{:02X}", c)?;
+                start = i + 1;
+            }
+        }
+        f.write_str(&self.0[start..])
+    }
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None
+    }
+}
+
+/// Decodes %XX escapes in `s`, if `plus_as_space` is true `+` is decoded as space
+/// (as in application/x-www-form-urlencoded)
+pub fn decode_bytes(s: &str, plus_as_space: bool) -> Result<Vec<u8>, Error> {
+    decode_with(s, plus_as_space, &[])
+}
+
+/// Decodes path segment, escapes of `/` (and `\`) are kept encoded,
+/// so decoded segment can never introduce new path separator
+#[cfg(feature = "std")]
+pub fn decode_segment(s: &str) -> Result<String, Error> {
+    decode_with(s, false, b"/\\")
+        .and_then(|v| String::from_utf8(v).map_err(|_| Error::InvalidEncoding))
+}
+
+fn decode_with(s: &str, plus_as_space: bool, keep: &[u8]) -> Result<Vec<u8>, Error> {
+    let b = s.as_bytes();
+    let mut res = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        match b[i] {
+            b'%' => {
+                if i + 2 >= b.len() {
+                    return Err(Error::InvalidEncoding)
+                }
+                match (hex_value(b[i+1]), hex_value(b[i+2])) {
+                    (Some(h), Some(l)) => {
+                        let c = h << 4 | l;
+                        if keep.contains(&c) {
+                            res.extend_from_slice(&b[i..i+3]);
+                        } else {
+                            res.push(c);
+                        }
+                    },
+                    _ => return Err(Error::InvalidEncoding)
+                }
+                i += 3;
+            },
+            b'+' if plus_as_space => {
+                res.push(b' ');
+                i += 1;
+            },
+            c => {
+                res.push(c);
+                i += 1;
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// Decodes %XX escapes in `s`, result must be valid UTF-8
+pub fn decode(s: &str, plus_as_space: bool) -> Result<String, Error> {
+    decode_bytes(s, plus_as_space)
+        .and_then(|v| String::from_utf8(v).map_err(|_| Error::InvalidEncoding))
+}
+
+/// Decodes application/x-www-form-urlencoded string - `+` is space, %XX escapes are decoded,
+/// but it never fails - malformed escapes are kept as they are and invalid UTF-8 is replaced
+#[cfg(feature = "std")]
+pub fn decode_form(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut res = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        match b[i] {
+            b'%' if i + 2 < b.len() => match (hex_value(b[i+1]), hex_value(b[i+2])) {
+                (Some(h), Some(l)) => {
+                    res.push(h << 4 | l);
+                    i += 3;
+                },
+                _ => {
+                    res.push(b'%');
+                    i += 1;
+                }
+            },
+            b'+' => {
+                res.push(b' ');
+                i += 1;
+            },
+            c => {
+                res.push(c);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("John%20Doe", false), Ok("John Doe".to_owned()));
+        assert_eq!(decode("S%C3%A3o", false), Ok("São".to_owned()));
+        assert_eq!(decode("a+b", false), Ok("a+b".to_owned()));
+        assert_eq!(decode("a+b", true), Ok("a b".to_owned()));
+        assert_eq!(decode("", false), Ok("".to_owned()));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(decode("%ZZ", false), Err(Error::InvalidEncoding));
+        assert_eq!(decode("abc%", false), Err(Error::InvalidEncoding));
+        assert_eq!(decode("abc%2", false), Err(Error::InvalidEncoding));
+        assert_eq!(decode("%FF", false), Err(Error::InvalidEncoding));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("%7euser"), "~user");
+        assert_eq!(normalize("a%2fb%c3%a9"), "a%2Fb%C3%A9");
+        assert_eq!(normalize("%41%42%2D"), "AB-");
+        assert_eq!(normalize("100%"), "100%");
+        assert_eq!(normalize("%zz%2"), "%zz%2");
+        assert_eq!(normalize("café"), "café");
+    }
+
+    #[test]
+    fn test_encode_component() {
+        assert_eq!(encode_component("a b&c/é", EncodeSet::Path), "a%20b&c%2F%C3%A9");
+        assert_eq!(encode_component("a b&c=d+e/é", EncodeSet::Query), "a%20b%26c%3Dd%2Be/%C3%A9");
+        assert_eq!(encode_component("a b&c#é", EncodeSet::Fragment), "a%20b&c%23%C3%A9");
+        assert_eq!(encode_component("a:b", EncodeSet::Path), "a:b");
+        assert_eq!(encode_component("a:b", EncodeSet::Query), "a:b");
+        assert_eq!(encode_component("a:b", EncodeSet::Fragment), "a:b");
+        let s = format!("http://h/{}?k={}#{}", encode_component("10:30 x", EncodeSet::Path),
+            encode_component("a:b&c", EncodeSet::Query), encode_component("f:g", EncodeSet::Fragment));
+        let u = super::super::parse_uri(&s).unwrap();
+        assert_eq!((u.path, u.query_get("k"), u.hash), (Some("/10:30%20x"), Some("a:b%26c"), Some("f:g")));
+        assert_eq!(encode_component("a b&c:d@é", EncodeSet::UserInfo), "a%20b&c%3Ad%40%C3%A9");
+        assert_eq!(encode_component("100%", EncodeSet::Query), "100%25");
+        assert_eq!(encode_component("safe-._~AZaz09", EncodeSet::Path), "safe-._~AZaz09");
+    }
+
+    #[test]
+    fn test_encoded_round_trip() {
+        for set in [EncodeSet::Path, EncodeSet::Query, EncodeSet::Fragment, EncodeSet::UserInfo].iter() {
+            let s = "a b&c=d+e/f?g#h:i@j%k€";
+            assert_eq!(decode(&encode_component(s, *set), false), Ok(s.to_owned()));
+        }
+    }
+
+    #[test]
+    fn test_escaped() {
+        assert_eq!(Escaped("a=b&c", QUERY_KEY).to_string(), "a%3Db%26c");
+        assert_eq!(Escaped("a=b&c:d", QUERY_VALUE).to_string(), "a=b%26c:d");
+        assert_eq!(Escaped("my doc", PATH).to_string(), "my%20doc");
+        assert_eq!(Escaped("already%20encoded", PATH).to_string(), "already%20encoded");
+        assert_eq!(Escaped("café", FRAGMENT).to_string(), "café");
+        assert_eq!(Escaped("", FRAGMENT).to_string(), "");
+        assert_eq!(Escaped("a[1]#b c", FRAGMENT).to_string(), "a[1]#b%20c");
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("a=b&c", QUERY_VALUE), "a=b%26c");
+        assert_eq!(encode("100% sure", PATH), "100%25%20sure");
+        assert_eq!(encode("café/x", HOST), "café%2Fx");
+        let s = encode("a%20b c", QUERY_KEY);
+        assert_eq!(Escaped(&s, QUERY_KEY).to_string(), s);
+        assert_eq!(decode(&s, false), Ok("a%20b c".to_owned()));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_decode_form() {
+        assert_eq!(decode_form("hello+world"), "hello world");
+        assert_eq!(decode_form("a%2Bb+c"), "a+b c");
+        assert_eq!(decode_form("caf%C3%A9"), "café");
+        assert_eq!(decode_form("100%+sure%2"), "100% sure%2");
+        assert_eq!(decode_form("%FF"), "\u{FFFD}");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_decode_segment() {
+        assert_eq!(decode_segment("my%20document.txt"), Ok("my document.txt".to_owned()));
+        assert_eq!(decode_segment("a%2Fb%2fc"), Ok("a%2Fb%2fc".to_owned()));
+        assert_eq!(decode_segment("a%5Cb"), Ok("a%5Cb".to_owned()));
+        assert_eq!(decode_segment("a%2"), Err(Error::InvalidEncoding));
+    }
+}
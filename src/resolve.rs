@@ -0,0 +1,192 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use super::{URI, UriBuf};
+
+fn non_empty(p: String) -> Option<String> {
+    if p.is_empty() {
+        None
+    } else {
+        Some(p)
+    }
+}
+
+// removes last segment including its leading /
+fn pop_segment(output: &mut String) {
+    match output.rfind('/') {
+        Some(i) => output.truncate(i),
+        None => output.clear()
+    }
+}
+
+/// Removes `.` and `..` segments from path as per RFC 3986 section 5.2.4
+pub fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output = String::with_capacity(path.len());
+    while !input.is_empty() {
+        if input.starts_with("../") {
+            input = &input[3..];
+        } else if input.starts_with("./") || input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") {
+            input = &input[3..];
+            pop_segment(&mut output);
+        } else if input == "/.." {
+            input = "/";
+            pop_segment(&mut output);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let start = if input.starts_with('/') {1} else {0};
+            let end = input[start..].find('/').map(|i| i + start).unwrap_or_else(|| input.len());
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output
+}
+
+// merges relative path with base path as per RFC 3986 section 5.2.3,
+// opaque part is path of opaque base
+fn merge(base: &URI, path: &str) -> String {
+    let base_path = base.path.or(base.opaque).unwrap_or("");
+    if base.host.is_some() && base_path.is_empty() {
+        format!("/{}", path)
+    } else {
+        match base_path.rfind('/') {
+            Some(i) => format!("{}{}", &base_path[..i+1], path),
+            None => path.to_owned()
+        }
+    }
+}
+
+/// Resolves reference against base URI as per RFC 3986 section 5.2
+/// Base is expected to be absolute URI. Opaque part of base (like `a@b` in `mailto:a@b`) is its path,
+/// so reference with path replaces it (`x` gives `mailto:x`). Target without authority and with
+/// rootless path is opaque, as it would be when parsed.
+pub fn resolve(base: &URI, reference: &URI) -> UriBuf {
+    let ref_path = reference.path.unwrap_or("");
+    let mut target = if reference.scheme.is_some() {
+        let mut t = reference.to_owned();
+        t.path = non_empty(remove_dot_segments(ref_path));
+        t
+    } else if reference.host.is_some() {
+        let mut t = reference.to_owned();
+        t.scheme = base.scheme.map(|s| s.to_owned());
+        t.path = non_empty(remove_dot_segments(ref_path));
+        t
+    } else {
+        let mut t = base.to_owned();
+        if ref_path.is_empty() {
+            if reference.query.is_some() {
+                t.query = reference.to_owned().query;
+            }
+        } else {
+            let path = if ref_path.starts_with('/') {
+                remove_dot_segments(ref_path)
+            } else {
+                remove_dot_segments(&merge(base, ref_path))
+            };
+            if t.host.is_none() && !path.starts_with('/') {
+                t.path = None;
+                t.opaque = non_empty(path);
+            } else {
+                t.path = non_empty(path);
+                t.opaque = None;
+            }
+            t.query = reference.to_owned().query;
+        }
+        t
+    };
+    target.hash = reference.hash.map(|h| h.to_owned());
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{parse_uri, parse_reference};
+
+    #[test]
+    fn test_remove_dot_segments() {
+        assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+        assert_eq!(remove_dot_segments("mid/content=5/../6"), "mid/6");
+        assert_eq!(remove_dot_segments("/a/b/../c/./d"), "/a/c/d");
+        assert_eq!(remove_dot_segments("../a"), "a");
+        assert_eq!(remove_dot_segments("/a/.."), "/");
+        assert_eq!(remove_dot_segments("/a/."), "/a/");
+        assert_eq!(remove_dot_segments("/a//b"), "/a//b");
+        assert_eq!(remove_dot_segments(""), "");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let base = parse_uri("http://a/b/c/d;p?q=1#f").unwrap();
+        let cases = [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y=1", "http://a/b/c/d;p?y=1"),
+            ("g?y=1", "http://a/b/c/g?y=1"),
+            ("#s", "http://a/b/c/d;p?q=1#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            (";x", "http://a/b/c/;x"),
+            ("", "http://a/b/c/d;p?q=1"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("../../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            (".g", "http://a/b/c/.g"),
+            ("g..", "http://a/b/c/g.."),
+            ("..g", "http://a/b/c/..g"),
+            ("./../g", "http://a/b/g"),
+            ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+        ];
+        for &(r, expected) in cases.iter() {
+            let reference = parse_reference(r).unwrap();
+            assert_eq!(resolve(&base, &reference).to_string(), expected, "resolving {}", r);
+        }
+    }
+
+    #[test]
+    fn test_resolve_empty_base_path() {
+        let base = parse_uri("http://a").unwrap();
+        let reference = parse_reference("g").unwrap();
+        assert_eq!(resolve(&base, &reference).to_string(), "http://a/g");
+    }
+
+    #[test]
+    fn test_resolve_opaque_base() {
+        let base = parse_uri("mailto:a@b").unwrap();
+        let tst = |r: &str| resolve(&base, &parse_reference(r).unwrap()).to_string();
+        assert_eq!(tst("x"), "mailto:x");
+        let r = resolve(&base, &parse_reference("x").unwrap());
+        assert!(r.as_uri().is_opaque());
+        assert_eq!(parse_uri("mailto:x").unwrap(), r.as_uri());
+        let r = resolve(&base, &parse_reference("/x").unwrap());
+        assert_eq!((r.path.as_deref(), r.opaque.as_deref()), (Some("/x"), None));
+        assert_eq!(tst("/x"), "mailto:/x");
+        assert_eq!(tst("../x?y=1"), "mailto:x?y=1");
+        let r = resolve(&parse_uri("tag:a/b").unwrap(), &parse_reference("c").unwrap());
+        assert_eq!(r.to_string(), "tag:a/c");
+        assert_eq!(parse_uri("tag:a/c").unwrap(), r.as_uri());
+        assert_eq!(tst("#f"), "mailto:a@b#f");
+        assert_eq!(tst("?subject=hi"), "mailto:a@b?subject=hi");
+        assert_eq!(tst("http://h/x"), "http://h/x");
+    }
+}
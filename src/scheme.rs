@@ -0,0 +1,382 @@
+use core::fmt;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use super::{URI, Error, ParseOptions, parse_with, until_whitespace_with};
+
+// IANA default ports of well known schemes
+const DEFAULT_PORTS: &[(&str, u16)] = &[
+    ("http", 80),
+    ("https", 443),
+    ("ftp", 21),
+    ("ftps", 990),
+    ("ssh", 22),
+    ("ws", 80),
+    ("wss", 443),
+];
+
+// well known service names (as in `/etc/services`)
+const SERVICES: &[(&str, u16)] = &[
+    ("ftp", 21),
+    ("ssh", 22),
+    ("telnet", 23),
+    ("smtp", 25),
+    ("domain", 53),
+    ("http", 80),
+    ("pop3", 110),
+    ("imap", 143),
+    ("ldap", 389),
+    ("https", 443),
+    ("imaps", 993),
+    ("pop3s", 995),
+];
+
+/// Returns port number of well known service name (compared case-insensitively)
+/// like `http` or `smtp`, `None` for unknown service
+pub fn service_port(name: &str) -> Option<u16> {
+    SERVICES.iter()
+        .find(|&&(s, _)| s.eq_ignore_ascii_case(name))
+        .map(|&(_, p)| p)
+}
+
+/// Returns default port for well known scheme (compared case-insensitively)
+/// or `None` for unknown scheme
+pub fn default_port(scheme: &str) -> Option<u16> {
+    DEFAULT_PORTS.iter()
+        .find(|&&(s, _)| s.eq_ignore_ascii_case(scheme))
+        .map(|&(_, p)| p)
+}
+
+/// Well known scheme classified case-insensitively (see `URI::known_scheme`),
+/// `Other` holds scheme as written (empty for URI without scheme)
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum KnownScheme<'a> {
+    Http,
+    Https,
+    Ftp,
+    File,
+    Mailto,
+    Ws,
+    Wss,
+    Other(&'a str)
+}
+
+impl <'a> From<&'a str> for KnownScheme<'a> {
+    fn from(scheme: &'a str) -> Self {
+        const KNOWN: &[(&str, KnownScheme<'static>)] = &[
+            ("http", KnownScheme::Http),
+            ("https", KnownScheme::Https),
+            ("ftp", KnownScheme::Ftp),
+            ("file", KnownScheme::File),
+            ("mailto", KnownScheme::Mailto),
+            ("ws", KnownScheme::Ws),
+            ("wss", KnownScheme::Wss),
+        ];
+        KNOWN.iter()
+            .find(|&&(s, _)| s.eq_ignore_ascii_case(scheme))
+            .map(|&(_, k)| k)
+            .unwrap_or(KnownScheme::Other(scheme))
+    }
+}
+
+// special schemes of WHATWG URL standard
+const SPECIAL: &[&str] = &["http", "https", "ws", "wss", "ftp", "file"];
+
+// true for special scheme (compared case-insensitively)
+pub(crate) fn is_special(scheme: &str) -> bool {
+    SPECIAL.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+}
+
+/// Scheme specific rules - parser looks up handler after scheme is read (custom handlers of `SchemeRegistry`
+/// first, then built-in ones). Handler can replace parsing of whole URI by `parse`, adjust generic grammar
+/// by `drive_letter` rule and validate parsed URI. Default rules are used if there is no handler.
+///
+/// There is no global registration, custom handlers are consulted only by methods of `SchemeRegistry`
+/// (`parse_uri`, `parse_uri_opts` and `parse_uri_until_whitespace`). Free functions like `parse_uri`
+/// or `parse_uri_until_whitespace` use built-in handlers only, `parse_uri_lazy` runs no handler
+/// (besides built-in `drive_letter` rule) until `LazyUri::to_uri`, which uses built-in handlers too
+pub trait SchemeHandler: Send + Sync {
+    /// Parses whole `input` (including scheme) instead of generic grammar, e.g. by composing combinators
+    /// of `parser` module. `None` (default) means generic grammar is used, returned URI is checked by `validate` too
+    fn parse<'a>(&self, _input: &'a [u8], _opts: &ParseOptions) -> Option<Result<URI<'a>, Error>> {
+        None
+    }
+
+    /// Windows drive letter can be in place of authority (like `file://C:/Users`),
+    /// it's parsed as path `C:/Users` with empty host
+    fn drive_letter(&self) -> bool {
+        false
+    }
+
+    /// Checks parsed URI, returned error is returned by parsing function
+    fn validate(&self, _uri: &URI<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// `http` and `https` - authority is required, empty host is accepted only with path (like `http:///path`),
+/// so bare `http://` is rejected
+#[derive(Debug,Clone,Copy)]
+pub struct HttpHandler;
+
+impl SchemeHandler for HttpHandler {
+    fn validate(&self, uri: &URI<'_>) -> Result<(), Error> {
+        let bare = uri.host == Some("") && uri.path.is_none();
+        if uri.host.is_none() || bare {
+            return Err(Error::InvalidHost)
+        }
+        Ok(())
+    }
+}
+
+/// `file` - empty host (`file:///etc/hosts`) and Windows drive letter in place of authority
+/// (`file://C:/Users`) are accepted
+#[derive(Debug,Clone,Copy)]
+pub struct FileHandler;
+
+impl SchemeHandler for FileHandler {
+    fn drive_letter(&self) -> bool {
+        true
+    }
+}
+
+/// `mailto` - only opaque part (addresses) is allowed, no authority or path
+#[derive(Debug,Clone,Copy)]
+pub struct MailtoHandler;
+
+impl SchemeHandler for MailtoHandler {
+    fn validate(&self, uri: &URI<'_>) -> Result<(), Error> {
+        if uri.host.is_some() || uri.path.is_some() {
+            return Err(Error::InvalidForScheme)
+        }
+        Ok(())
+    }
+}
+
+/// `urn` - opaque part must be `NID:NSS` with non-empty namespace identifier (RFC 8141)
+#[derive(Debug,Clone,Copy)]
+pub struct UrnHandler;
+
+impl SchemeHandler for UrnHandler {
+    fn validate(&self, uri: &URI<'_>) -> Result<(), Error> {
+        let valid = uri.opaque.and_then(|o| o.find(':').map(|i| i > 0 && i + 1 < o.len()));
+        if valid != Some(true) {
+            return Err(Error::InvalidForScheme)
+        }
+        Ok(())
+    }
+}
+
+/// Custom scheme handlers used by `SchemeRegistry::parse_uri`, they take precedence over built-in handlers.
+/// Registry is plain value, so each caller decides which handlers its parsing uses,
+/// `parse_uri` and other parsing functions use built-in handlers only
+///
+/// ```
+/// use uri_parser::{SchemeRegistry, SchemeHandler, URI, Error};
+///
+/// struct NoQuery;
+///
+/// impl SchemeHandler for NoQuery {
+///     fn validate(&self, uri: &URI<'_>) -> Result<(), Error> {
+///         if uri.query.is_some() {
+///             return Err(Error::InvalidForScheme)
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut registry = SchemeRegistry::new();
+/// registry.register("x-local", NoQuery);
+/// assert!(registry.parse_uri("x-local:///tmp/sock").is_ok());
+/// assert_eq!(registry.parse_uri("x-local:///tmp/sock?a=1"), Err(Error::InvalidForScheme));
+/// ```
+#[derive(Clone,Default)]
+pub struct SchemeRegistry {
+    handlers: Vec<(String, Arc<dyn SchemeHandler>)>
+}
+
+impl SchemeRegistry {
+    /// Registry without custom handlers (only built-in handlers are used)
+    pub const fn new() -> Self {
+        SchemeRegistry {handlers: Vec::new()}
+    }
+
+    /// Registers handler for scheme (compared case-insensitively), it replaces built-in
+    /// or previously registered handler of same scheme
+    pub fn register<H: SchemeHandler + 'static>(&mut self, name: &str, handler: H) {
+        self.handlers.retain(|(s, _)| !s.eq_ignore_ascii_case(name));
+        self.handlers.push((name.to_owned(), Arc::new(handler)));
+    }
+
+    /// Handler of scheme - registered one or built-in one, `None` if scheme has no handler
+    pub fn handler(&self, scheme: &str) -> Option<&dyn SchemeHandler> {
+        self.handlers.iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(scheme))
+            .map(|(_, h)| h.as_ref())
+            .or_else(|| built_in(scheme))
+    }
+
+    /// Parses URI like `parse_uri`, but with handlers of this registry
+    pub fn parse_uri<'a, T: AsRef<[u8]>+?Sized>(&self, uri_string: &'a T) -> Result<URI<'a>, Error> {
+        self.parse_uri_opts(uri_string, &ParseOptions::default())
+    }
+
+    /// Parses URI like `parse_uri_opts`, but with handlers of this registry
+    pub fn parse_uri_opts<'a, T: AsRef<[u8]>+?Sized>(&self, uri_string: &'a T, opts: &ParseOptions) -> Result<URI<'a>, Error> {
+        parse_with(uri_string.as_ref(), opts, self)
+    }
+
+    /// Parses whitespace-delimited URI like `parse_uri_until_whitespace_opts`, but with handlers of this registry
+    pub fn parse_uri_until_whitespace<'a, T: AsRef<[u8]>+?Sized>(&self, input: &'a T, opts: &ParseOptions)
+        -> Result<(URI<'a>, &'a [u8]), Error> {
+        until_whitespace_with(input.as_ref(), opts, self)
+    }
+}
+
+impl fmt::Debug for SchemeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.handlers.iter().map(|(s, _)| s)).finish()
+    }
+}
+
+const BUILT_IN: &[(&str, &dyn SchemeHandler)] = &[
+    ("http", &HttpHandler),
+    ("https", &HttpHandler),
+    ("file", &FileHandler),
+    ("mailto", &MailtoHandler),
+    ("urn", &UrnHandler),
+];
+
+// built-in handler of scheme
+fn built_in(scheme: &str) -> Option<&'static dyn SchemeHandler> {
+    BUILT_IN.iter().find(|&&(s, _)| s.eq_ignore_ascii_case(scheme)).map(|&(_, h)| h)
+}
+
+// runs handler validation of URI with scheme
+pub(crate) fn validate(uri: &URI<'_>, registry: &SchemeRegistry) -> Result<(), Error> {
+    match uri.scheme.and_then(|s| registry.handler(s)) {
+        Some(h) => h.validate(uri),
+        None => Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{parse_uri, parse_uri_until_whitespace};
+
+    #[test]
+    fn test_default_port() {
+        assert_eq!(default_port("http"), Some(80));
+        assert_eq!(default_port("HTTPS"), Some(443));
+        assert_eq!(default_port("ftp"), Some(21));
+        assert_eq!(default_port("ftps"), Some(990));
+        assert_eq!(default_port("ssh"), Some(22));
+        assert_eq!(default_port("ws"), Some(80));
+        assert_eq!(default_port("wss"), Some(443));
+        assert_eq!(default_port("gopher"), None);
+    }
+
+    #[test]
+    fn test_known_scheme() {
+        assert_eq!(KnownScheme::from("HTTPS"), KnownScheme::Https);
+        assert_eq!(KnownScheme::from("http"), KnownScheme::Http);
+        assert_eq!(KnownScheme::from("MailTo"), KnownScheme::Mailto);
+        assert_eq!(KnownScheme::from("Gopher"), KnownScheme::Other("Gopher"));
+        assert_eq!(parse_uri("WSS://h/").unwrap().known_scheme(), KnownScheme::Wss);
+        assert_eq!(parse_uri("s3://bucket/key").unwrap().known_scheme(), KnownScheme::Other("s3"));
+        assert_eq!(super::super::parse_reference("/p").unwrap().known_scheme(), KnownScheme::Other(""));
+    }
+
+    #[test]
+    fn test_service_port() {
+        assert_eq!(service_port("http"), Some(80));
+        assert_eq!(service_port("SMTP"), Some(25));
+        assert_eq!(service_port("imaps"), Some(993));
+        assert_eq!(service_port("x-custom"), None);
+    }
+
+    #[test]
+    fn test_built_in_handlers() {
+        assert_eq!(parse_uri("http:foo"), Err(Error::InvalidHost));
+        assert_eq!(parse_uri("https:/path"), Err(Error::InvalidHost));
+        assert_eq!(parse_uri("http://"), Err(Error::InvalidHost));
+        assert_eq!(parse_uri("https:///path").unwrap().host, Some(""));
+        assert!(parse_uri("file:///etc/hosts").is_ok());
+        assert!(parse_uri("file://").is_ok());
+        assert_eq!(parse_uri("file://C:/Users").unwrap().path, Some("C:/Users"));
+        assert!(parse_uri("mailto:a@b.com").is_ok());
+        assert_eq!(parse_uri("mailto://a@b.com"), Err(Error::InvalidForScheme));
+        assert!(parse_uri("urn:isbn:0451450523").is_ok());
+        assert_eq!(parse_uri("urn:isbn"), Err(Error::InvalidForScheme));
+        assert_eq!(parse_uri("urn::x"), Err(Error::InvalidForScheme));
+        assert_eq!(parse_uri("URN:isbn:"), Err(Error::InvalidForScheme));
+    }
+
+    struct Local;
+
+    impl SchemeHandler for Local {
+        fn validate(&self, uri: &URI<'_>) -> Result<(), Error> {
+            if uri.query.is_some() {
+                return Err(Error::InvalidForScheme)
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = SchemeRegistry::new();
+        registry.register("X-Local", Local);
+        let u = registry.parse_uri("x-local:///tmp/sock").unwrap();
+        assert_eq!(u.host, Some(""));
+        assert_eq!(u.path, Some("/tmp/sock"));
+        assert_eq!(registry.parse_uri("x-local:///tmp/sock?a=1"), Err(Error::InvalidForScheme));
+        assert!(registry.parse_uri("x-other:///tmp/sock?a=1").is_ok());
+        assert_eq!(registry.parse_uri("http://"), Err(Error::InvalidHost));
+        // other parsing is not affected
+        assert!(parse_uri("x-local:///tmp/sock?a=1").is_ok());
+
+        registry.register("http", Local);
+        assert!(registry.parse_uri("http:foo").is_ok());
+        assert_eq!(parse_uri("http:foo"), Err(Error::InvalidHost));
+    }
+
+    // everything after scheme is opaque, including `?` and `#`
+    struct Script;
+
+    impl SchemeHandler for Script {
+        fn parse<'a>(&self, input: &'a [u8], _opts: &ParseOptions) -> Option<Result<URI<'a>, Error>> {
+            let s = match ::std::str::from_utf8(input) {
+                Ok(s) => s,
+                Err(_) => return Some(Err(Error::InvalidEncoding))
+            };
+            let colon = s.find(':')?;
+            Some(Ok(URI{scheme: Some(&s[..colon]), opaque: Some(&s[colon+1..]), ..Default::default()}))
+        }
+
+        fn validate(&self, uri: &URI<'_>) -> Result<(), Error> {
+            if uri.opaque.map(|o| o.is_empty()).unwrap_or(true) {
+                return Err(Error::InvalidForScheme)
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_handler_parse() {
+        let mut registry = SchemeRegistry::new();
+        registry.register("javascript", Script);
+        let u = registry.parse_uri("javascript:f('a?b#c')").unwrap();
+        assert_eq!(u.opaque, Some("f('a?b#c')"));
+        assert_eq!(u.query, None);
+        assert_eq!(registry.parse_uri("javascript:"), Err(Error::InvalidForScheme));
+        assert_eq!(parse_uri("javascript:f('a?b#c')").unwrap().opaque, Some("f('a"));
+        let (u, rest) = registry.parse_uri_until_whitespace("javascript:f('a?b#c') next", &ParseOptions::default()).unwrap();
+        assert_eq!((u.opaque, rest), (Some("f('a?b#c')"), &b" next"[..]));
+        assert_eq!(registry.parse_uri_until_whitespace("javascript: next", &ParseOptions::default()), Err(Error::InvalidForScheme));
+        assert_eq!(parse_uri_until_whitespace("javascript:f('a?b#c') next").unwrap().0.opaque, Some("f('a"));
+        assert_eq!(format!("{:?}", registry), "[\"javascript\"]");
+    }
+}
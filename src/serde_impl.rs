@@ -0,0 +1,51 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error as DeError;
+use alloc::string::String;
+use super::{URI, UriBuf};
+
+// URIs are (de)serialized as single string
+
+impl <'a> Serialize for URI<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for UriBuf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl <'de> Deserialize<'de> for UriBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use super::super::parse_uri;
+
+    #[test]
+    fn test_serialize() {
+        let u = parse_uri("http://www.example.com:8080/a?b=c#d").unwrap();
+        assert_eq!(serde_json::to_string(&u).unwrap(), r#""http://www.example.com:8080/a?b=c#d""#);
+        assert_eq!(serde_json::to_string(&u.to_owned()).unwrap(), r#""http://www.example.com:8080/a?b=c#d""#);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let u: UriBuf = serde_json::from_str(r#""http://www.example.com:8080/a?b=c#d""#).unwrap();
+        assert_eq!(u.host, Some("www.example.com".to_owned()));
+        assert_eq!(u.port, Some(8080));
+        let round_trip: UriBuf = serde_json::from_str(&serde_json::to_string(&u).unwrap()).unwrap();
+        assert_eq!(round_trip, u);
+
+        assert!(serde_json::from_str::<UriBuf>(r#""not a uri""#).is_err());
+        assert!(serde_json::from_str::<UriBuf>("42").is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use super::{URI, Error};
+
+// strict validation of already parsed URI components against RFC 3986 grammar,
+// all components are slices of the input, so offset is computed from pointers
+
+const SUB_DELIMS: &[u8] = b"!$&'()*+,;=";
+
+fn is_unreserved(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"-._~".contains(&c)
+}
+
+fn is_host_char(c: u8) -> bool {
+    is_unreserved(c) || SUB_DELIMS.contains(&c)
+}
+
+fn is_user_char(c: u8) -> bool {
+    is_host_char(c) || c == b':'
+}
+
+fn is_pchar(c: u8) -> bool {
+    is_host_char(c) || c == b':' || c == b'@'
+}
+
+fn is_path_char(c: u8) -> bool {
+    is_pchar(c) || c == b'/'
+}
+
+fn is_query_char(c: u8) -> bool {
+    is_path_char(c) || c == b'?'
+}
+
+// empty component may be static `""` created by parser (not slice of input), there is nothing to check
+fn check(input: &[u8], component: &str, allowed: fn(u8) -> bool) -> Result<(), Error> {
+    if component.is_empty() {
+        return Ok(())
+    }
+    let start = component.as_ptr() as usize - input.as_ptr() as usize;
+    let b = component.as_bytes();
+    let mut i = 0;
+    while i < b.len() {
+        let c = b[i];
+        if c == b'%' {
+            if i + 2 >= b.len() || !b[i+1].is_ascii_hexdigit() || !b[i+2].is_ascii_hexdigit() {
+                return Err(Error::IllegalCharacter{byte: c, offset: start + i})
+            }
+            i += 3;
+        } else if allowed(c) {
+            i += 1;
+        } else {
+            return Err(Error::IllegalCharacter{byte: c, offset: start + i})
+        }
+    }
+    Ok(())
+}
+
+/// Checks that all components contain only characters allowed by RFC 3986
+pub fn validate(input: &[u8], uri: &URI) -> Result<(), Error> {
+    if let Some(ref user) = uri.user {
+        check(input, user.name, is_user_char)?;
+        if let Some(password) = user.password {
+            check(input, password, is_user_char)?;
+        }
+    }
+    if let Some(host) = uri.host {
+        // IP literal is already restricted by parser
+        if !host.contains(':') {
+            check(input, host, is_host_char)?;
+        }
+    }
+    if let Some(path) = uri.path {
+        check(input, path, is_path_char)?;
+    }
+    if let Some(opaque) = uri.opaque {
+        check(input, opaque, is_path_char)?;
+    }
+    if let Some(ref query) = uri.query {
+        for &(k, v) in query {
+            check(input, k, is_query_char)?;
+            check(input, v, is_query_char)?;
+        }
+    }
+    if let Some(hash) = uri.hash {
+        check(input, hash, is_query_char)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::parse_uri;
+
+    fn strict(s: &str) -> Result<(), Error> {
+        validate(s.as_bytes(), &parse_uri(s).unwrap())
+    }
+
+    #[test]
+    fn test_valid() {
+        assert_eq!(strict("http://user:pw@www.example.com:8080/a/b;p=1?q=a/b?c#frag/x?y"), Ok(()));
+        assert_eq!(strict("http://h/caf%C3%A9"), Ok(()));
+        assert_eq!(strict("http://[::1]/"), Ok(()));
+        assert_eq!(strict("mailto:a@b.com"), Ok(()));
+        assert_eq!(strict("file:///etc/hosts"), Ok(()));
+        assert_eq!(strict("http://h/?flag"), Ok(()));
+        assert_eq!(strict("http://h/?a="), Ok(()));
+        assert_eq!(strict("ftp://u@/p"), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(strict("http://exa{mple.com/"), Err(Error::IllegalCharacter{byte: b'{', offset: 10}));
+        assert_eq!(strict("http://h/a b"), Err(Error::IllegalCharacter{byte: b' ', offset: 10}));
+        assert_eq!(strict("http://h/a%2"), Err(Error::IllegalCharacter{byte: b'%', offset: 10}));
+        assert_eq!(strict("http://h/a%zz"), Err(Error::IllegalCharacter{byte: b'%', offset: 10}));
+        assert_eq!(strict("http://h/?q=a|b"), Err(Error::IllegalCharacter{byte: b'|', offset: 13}));
+        assert_eq!(strict("http://h/#a\"b"), Err(Error::IllegalCharacter{byte: b'"', offset: 11}));
+        assert_eq!(strict("http://us<er@h/"), Err(Error::IllegalCharacter{byte: b'<', offset: 9}));
+        assert_eq!(strict("http://h/caf\u{e9}"), Err(Error::IllegalCharacter{byte: 0xc3, offset: 12}));
+    }
+}
@@ -0,0 +1,46 @@
+// `parse_uri_lazy` must not allocate, it's checked by counting allocator,
+// which is installed in this separate test binary, so it doesn't affect unit tests
+extern crate uri_parser;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use uri_parser::{parse_uri, parse_uri_lazy};
+
+// counts allocations of current thread, so tests running in parallel don't interfere
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(|a| a.get());
+    f();
+    ALLOCATIONS.with(|a| a.get()) - before
+}
+
+#[test]
+fn test_lazy_no_allocation() {
+    let s = "https://user:pw@www.example.com:8443/a/b/c?q=rust&page=2&tag=a&tag=b#top";
+    let n = allocations(|| {
+        let u = parse_uri_lazy(s).unwrap();
+        assert_eq!(u.host().unwrap(), Some("www.example.com"));
+        assert_eq!(u.query_pairs().find(|&(k, _)| k == "page"), Some(("page", "2")));
+    });
+    assert_eq!(n, 0);
+    assert!(allocations(|| { parse_uri(s).unwrap(); }) > 0);
+}